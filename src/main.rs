@@ -3,6 +3,11 @@ use rayon::prelude::*;
 use std::time::Instant;
 use tracing::{info, instrument, span, Level};
 
+//lane width for the batched force kernel; sources are processed LANES at a time so the
+//inner loop stays a fixed-size, auto-vectorizable shape without requiring the nightly-only
+//std::simd API
+const LANES: usize = 4;
+
 //using "Copy" traits for raw performance on simple structs
 #[derive(Clone, Copy, Debug)]
 struct Body {
@@ -27,15 +32,52 @@ struct Args {
     #[arg(short, long, value_enum, default_value_t = Mode::Parallel)]
     mode: Mode,
 
+    //time-stepping scheme used to advance the system each tick
+    #[arg(short, long, value_enum, default_value_t = Integrator::Euler)]
+    integrator: Integrator,
+
     //number of simulation ticks
     #[arg(short, long, default_value_t = 100)]
     ticks: usize,
+
+    //log energy/momentum every N ticks, in addition to tick 0 and the final tick
+    #[arg(long)]
+    report_interval: Option<usize>,
+
+    //Barnes-Hut opening angle: smaller is more accurate but slower
+    #[arg(long, default_value_t = 0.5)]
+    theta: f64,
+
+    //detect when the system returns close to its initial state and report the period
+    #[arg(long)]
+    detect_recurrence: bool,
+
+    //normalized state distance below which the system counts as "recurred"
+    #[arg(long, default_value_t = 1e-3)]
+    recurrence_tol: f64,
+
+    //compute and report total-energy diagnostics (kinetic + potential). This is an extra
+    //O(N^2) pass over all bodies on top of the simulation itself, so it's opt-in: at the body
+    //counts Barnes-Hut/SIMD are meant for, an unconditional energy snapshot would dominate
+    //wall-clock time over the O(N log N)/SIMD step it's meant to be measuring.
+    #[arg(long)]
+    energy: bool,
 }
 
 #[derive(ValueEnum, Clone, Debug)]
 enum Mode {
     Serial,
     Parallel,
+    Simd,
+    BarnesHut,
+    Gpu,
+}
+
+#[derive(ValueEnum, Clone, Debug)]
+enum Integrator {
+    Euler,
+    Verlet,
+    Rk4,
 }
 
 impl Universe {
@@ -67,6 +109,25 @@ impl Universe {
             });
         }
 
+        //cancel bulk momentum so the cluster doesn't drift as a whole (the "offset
+        //momentum" trick from the benchmark-game nbody seeds)
+        let total_mass: f64 = bodies.iter().map(|b| b.mass).sum();
+        let mut mean_vel = [0.0; 3];
+        for body in &bodies {
+            mean_vel[0] += body.mass * body.vel[0];
+            mean_vel[1] += body.mass * body.vel[1];
+            mean_vel[2] += body.mass * body.vel[2];
+        }
+        mean_vel[0] /= total_mass;
+        mean_vel[1] /= total_mass;
+        mean_vel[2] /= total_mass;
+
+        for body in &mut bodies {
+            body.vel[0] -= mean_vel[0];
+            body.vel[1] -= mean_vel[1];
+            body.vel[2] -= mean_vel[2];
+        }
+
         info!("Universe created with {} bodies.", count);
         Universe {
             bodies,
@@ -75,41 +136,138 @@ impl Universe {
         }
     }
 
-    //acceleration on target caused by source
+    //total kinetic + potential energy; potential uses the same softening as compute_force
+    fn total_energy(&self) -> f64 {
+        let kinetic: f64 = self
+            .bodies
+            .iter()
+            .map(|b| 0.5 * b.mass * (b.vel[0] * b.vel[0] + b.vel[1] * b.vel[1] + b.vel[2] * b.vel[2]))
+            .sum();
+
+        let mut potential = 0.0;
+        for i in 0..self.bodies.len() {
+            for j in (i + 1)..self.bodies.len() {
+                let a = &self.bodies[i];
+                let b = &self.bodies[j];
+                let dx = a.pos[0] - b.pos[0];
+                let dy = a.pos[1] - b.pos[1];
+                let dz = a.pos[2] - b.pos[2];
+                let dist = (dx * dx + dy * dy + dz * dz + self.softening).sqrt();
+                potential -= self.g_const * a.mass * b.mass / dist;
+            }
+        }
+
+        kinetic + potential
+    }
+
+    //total linear momentum, expected to stay near zero after the Universe::new offset
+    fn total_momentum(&self) -> [f64; 3] {
+        let mut momentum = [0.0; 3];
+        for body in &self.bodies {
+            momentum[0] += body.mass * body.vel[0];
+            momentum[1] += body.mass * body.vel[1];
+            momentum[2] += body.mass * body.vel[2];
+        }
+        momentum
+    }
+
+    //acceleration on target_pos caused by a source at source_pos
     #[inline(always)]
-    fn compute_force(&self, target: &Body, source: &Body) -> [f64; 3] {
-        let dx = source.pos[0] - target.pos[0];
-        let dy = source.pos[1] - target.pos[1];
-        let dz = source.pos[2] - target.pos[2];
+    fn compute_force(&self, target_pos: [f64; 3], source_pos: [f64; 3], source_mass: f64) -> [f64; 3] {
+        softened_force(target_pos, source_pos, source_mass, self.g_const, self.softening)
+    }
+
+    fn positions(&self) -> Vec<[f64; 3]> {
+        self.bodies.iter().map(|b| b.pos).collect()
+    }
+
+    fn velocities(&self) -> Vec<[f64; 3]> {
+        self.bodies.iter().map(|b| b.vel).collect()
+    }
+
+    //RMS distance between the current (pos, vel) state and a prior snapshot, over all bodies
+    fn state_distance(&self, other_pos: &[[f64; 3]], other_vel: &[[f64; 3]]) -> f64 {
+        let n = self.bodies.len();
+        let sum_sq: f64 = self
+            .bodies
+            .iter()
+            .zip(other_pos.iter())
+            .zip(other_vel.iter())
+            .map(|((body, pos), vel)| {
+                let dx = body.pos[0] - pos[0];
+                let dy = body.pos[1] - pos[1];
+                let dz = body.pos[2] - pos[2];
+                let dvx = body.vel[0] - vel[0];
+                let dvy = body.vel[1] - vel[1];
+                let dvz = body.vel[2] - vel[2];
+                dx * dx + dy * dy + dz * dz + dvx * dvx + dvy * dvy + dvz * dvz
+            })
+            .sum();
 
-        let dist_sq = dx * dx + dy * dy + dz * dz + self.softening;
-        let dist = dist_sq.sqrt();
-        let f = (self.g_const * source.mass) / dist_sq;
+        (sum_sq / n as f64).sqrt()
+    }
 
-        [f * dx / dist, f * dy / dist, f * dz / dist]
+    //RMS magnitude of the current (pos, vel) state, used to normalize state_distance
+    fn state_scale(&self) -> f64 {
+        self.state_distance(&vec![[0.0; 3]; self.bodies.len()], &vec![[0.0; 3]; self.bodies.len()])
+    }
+
+    //accelerations on every body at its current position, for the Mode-selected backend
+    fn accelerations(&self, mode: &Mode, theta: f64) -> Vec<[f64; 3]> {
+        self.accelerations_at(&self.positions(), mode, theta)
+    }
+
+    //the shared force kernel: every integrator (Euler/Verlet/Rk4) re-evaluates this at
+    //whatever positions its scheme calls for, so it takes an explicit position snapshot
+    //rather than always reading self.bodies. `theta` only matters for Mode::BarnesHut.
+    fn accelerations_at(&self, positions: &[[f64; 3]], mode: &Mode, theta: f64) -> Vec<[f64; 3]> {
+        match mode {
+            Mode::Serial => self.accelerations_serial(positions),
+            Mode::Parallel => self.accelerations_parallel(positions),
+            Mode::Simd => self.accelerations_simd(positions),
+            Mode::BarnesHut => self.accelerations_barnes_hut(positions, theta),
+            Mode::Gpu => self.accelerations_gpu(positions),
+        }
+    }
+
+    //serial iterator
+    fn accelerations_serial(&self, positions: &[[f64; 3]]) -> Vec<[f64; 3]> {
+        positions
+            .iter()
+            .enumerate()
+            .map(|(i, target_pos)| {
+                let mut acc = [0.0; 3];
+                for (j, source_pos) in positions.iter().enumerate() {
+                    if self.bodies[i].id != self.bodies[j].id {
+                        let f = self.compute_force(*target_pos, *source_pos, self.bodies[j].mass);
+                        acc[0] += f[0];
+                        acc[1] += f[1];
+                        acc[2] += f[2];
+                    }
+                }
+                acc
+            })
+            .collect()
     }
 
     //rayon parallel iterator
-    #[instrument(skip(self), name = "tick_parallel")]
-    fn step_parallel(&mut self, dt: f64) {
-        let positions: Vec<[f64; 3]> = self.bodies.iter().map(|b| b.pos).collect();
+    fn accelerations_parallel(&self, positions: &[[f64; 3]]) -> Vec<[f64; 3]> {
         let masses: Vec<f64> = self.bodies.iter().map(|b| b.mass).collect();
 
-        //computing accelerations in parallel
-        let accelerations: Vec<[f64; 3]> = self.bodies
-            .par_iter()
-            .map(|body| {
+        (0..positions.len())
+            .into_par_iter()
+            .map(|i| {
                 let mut acc = [0.0; 3];
                 //iterating over the separated data to avoid borrowing the whole body struct
-                for (i, pos) in positions.iter().enumerate() {
-                    if body.id != i {
-                        let dx = pos[0] - body.pos[0];
-                        let dy = pos[1] - body.pos[1];
-                        let dz = pos[2] - body.pos[2];
+                for (j, pos) in positions.iter().enumerate() {
+                    if self.bodies[i].id != self.bodies[j].id {
+                        let dx = pos[0] - positions[i][0];
+                        let dy = pos[1] - positions[i][1];
+                        let dz = pos[2] - positions[i][2];
 
                         let dist_sq = dx * dx + dy * dy + dz * dz + self.softening;
                         let dist = dist_sq.sqrt();
-                        let f = (self.g_const * masses[i]) / dist_sq;
+                        let f = (self.g_const * masses[j]) / dist_sq;
 
                         acc[0] += f * dx / dist;
                         acc[1] += f * dy / dist;
@@ -118,39 +276,138 @@ impl Universe {
                 }
                 acc
             })
-            .collect();
-
-        self.bodies.par_iter_mut().zip(accelerations.par_iter()).for_each(|(body, acc)| {
-            body.vel[0] += acc[0] * dt;
-            body.vel[1] += acc[1] * dt;
-            body.vel[2] += acc[2] * dt;
-            body.pos[0] += body.vel[0] * dt;
-            body.pos[1] += body.vel[1] * dt;
-            body.pos[2] += body.vel[2] * dt;
-        });
+            .collect()
     }
 
-    //serial iterator
-    #[instrument(skip(self), name = "tick_serial")]
-    fn step_serial(&mut self, dt: f64) {
-        let updates: Vec<[f64; 3]> = self.bodies
-            .iter()
-            .map(|body| {
+    //structure-of-arrays force kernel, LANES source bodies at a time. The inner loop is
+    //manually unrolled over fixed-size LANES arrays (rather than std::simd) so the kernel
+    //builds on stable Rust; LLVM auto-vectorizes this shape on most targets.
+    fn accelerations_simd(&self, positions: &[[f64; 3]]) -> Vec<[f64; 3]> {
+        let n = positions.len();
+        let mut xs = vec![0.0; n];
+        let mut ys = vec![0.0; n];
+        let mut zs = vec![0.0; n];
+        let mut masses = vec![0.0; n];
+        for (i, pos) in positions.iter().enumerate() {
+            xs[i] = pos[0];
+            ys[i] = pos[1];
+            zs[i] = pos[2];
+            masses[i] = self.bodies[i].mass;
+        }
+
+        (0..n)
+            .into_par_iter()
+            .map(|target| {
+                let tx = xs[target];
+                let ty = ys[target];
+                let tz = zs[target];
+
                 let mut acc = [0.0; 3];
-                for other in &self.bodies {
-                    if body.id != other.id {
-                        let f = self.compute_force(body, other);
-                        acc[0] += f[0];
-                        acc[1] += f[1];
-                        acc[2] += f[2];
+                let chunks = n / LANES;
+
+                for chunk in 0..chunks {
+                    let base = chunk * LANES;
+                    let mut fx = [0.0; LANES];
+                    let mut fy = [0.0; LANES];
+                    let mut fz = [0.0; LANES];
+
+                    //unrolled over the lane width; the self-interaction lane (source ==
+                    //target) is simply skipped rather than masked after the fact
+                    for lane in 0..LANES {
+                        let source = base + lane;
+                        if source == target {
+                            continue;
+                        }
+
+                        let dx = xs[source] - tx;
+                        let dy = ys[source] - ty;
+                        let dz = zs[source] - tz;
+
+                        let dist_sq = dx * dx + dy * dy + dz * dz + self.softening;
+                        let inv_dist = dist_sq.sqrt().recip();
+                        let inv_dist_sq = inv_dist * inv_dist;
+                        let f = self.g_const * masses[source] * inv_dist_sq * inv_dist;
+
+                        fx[lane] = f * dx;
+                        fy[lane] = f * dy;
+                        fz[lane] = f * dz;
                     }
+
+                    acc[0] += fx.iter().sum::<f64>();
+                    acc[1] += fy.iter().sum::<f64>();
+                    acc[2] += fz.iter().sum::<f64>();
                 }
+
+                //scalar remainder for a source count not divisible by LANES
+                for source in (chunks * LANES)..n {
+                    if source != target {
+                        let dx = xs[source] - xs[target];
+                        let dy = ys[source] - ys[target];
+                        let dz = zs[source] - zs[target];
+
+                        let dist_sq = dx * dx + dy * dy + dz * dz + self.softening;
+                        let dist = dist_sq.sqrt();
+                        let f = (self.g_const * masses[source]) / dist_sq;
+
+                        acc[0] += f * dx / dist;
+                        acc[1] += f * dy / dist;
+                        acc[2] += f * dz / dist;
+                    }
+                }
+
                 acc
             })
-            .collect();
+            .collect()
+    }
 
-        for (i, body) in self.bodies.iter_mut().enumerate() {
-            let acc = updates[i];
+    //O(N log N) approximation: build an octree over the bounding cube each tick, then walk
+    //it per body, substituting a distant node's center-of-mass for its full contents once
+    //cell_width / distance < theta
+    fn accelerations_barnes_hut(&self, positions: &[[f64; 3]], theta: f64) -> Vec<[f64; 3]> {
+        let masses: Vec<f64> = self.bodies.iter().map(|b| b.mass).collect();
+        let tree = Octree::build(positions, &masses);
+
+        (0..positions.len())
+            .into_par_iter()
+            .map(|i| tree.acceleration_on(positions[i], i, theta, self.g_const, self.softening))
+            .collect()
+    }
+
+    //offloads the O(N^2) kernel to the GPU when the `gpu` feature is enabled and a device is
+    //present; falls back to the CPU parallel path otherwise
+    #[cfg(feature = "gpu")]
+    fn accelerations_gpu(&self, positions: &[[f64; 3]]) -> Vec<[f64; 3]> {
+        let masses: Vec<f64> = self.bodies.iter().map(|b| b.mass).collect();
+        match gpu::compute_accelerations(positions, &masses, self.g_const, self.softening) {
+            Some(accelerations) => accelerations,
+            None => {
+                info!("no GPU device available, falling back to the parallel CPU kernel");
+                self.accelerations_parallel(positions)
+            }
+        }
+    }
+
+    #[cfg(not(feature = "gpu"))]
+    fn accelerations_gpu(&self, positions: &[[f64; 3]]) -> Vec<[f64; 3]> {
+        self.accelerations_parallel(positions)
+    }
+
+    //advances the universe by dt using the requested integrator, with accelerations
+    //evaluated by the requested Mode backend
+    fn step(&mut self, dt: f64, mode: &Mode, integrator: &Integrator, theta: f64) {
+        match integrator {
+            Integrator::Euler => self.step_euler(dt, mode, theta),
+            Integrator::Verlet => self.step_verlet(dt, mode, theta),
+            Integrator::Rk4 => self.step_rk4(dt, mode, theta),
+        }
+    }
+
+    //semi-implicit Euler: v += a*dt; x += v*dt
+    #[instrument(skip(self), name = "tick_euler")]
+    fn step_euler(&mut self, dt: f64, mode: &Mode, theta: f64) {
+        let accelerations = self.accelerations(mode, theta);
+
+        for (body, acc) in self.bodies.iter_mut().zip(accelerations.iter()) {
             body.vel[0] += acc[0] * dt;
             body.vel[1] += acc[1] * dt;
             body.vel[2] += acc[2] * dt;
@@ -159,6 +416,367 @@ impl Universe {
             body.pos[2] += body.vel[2] * dt;
         }
     }
+
+    //velocity-Verlet (leapfrog): symplectic, conserves energy far better than Euler
+    #[instrument(skip(self), name = "tick_verlet")]
+    fn step_verlet(&mut self, dt: f64, mode: &Mode, theta: f64) {
+        let a_old = self.accelerations(mode, theta);
+
+        let new_positions: Vec<[f64; 3]> = self
+            .bodies
+            .iter()
+            .zip(a_old.iter())
+            .map(|(body, a)| {
+                [
+                    body.pos[0] + body.vel[0] * dt + 0.5 * a[0] * dt * dt,
+                    body.pos[1] + body.vel[1] * dt + 0.5 * a[1] * dt * dt,
+                    body.pos[2] + body.vel[2] * dt + 0.5 * a[2] * dt * dt,
+                ]
+            })
+            .collect();
+
+        let a_new = self.accelerations_at(&new_positions, mode, theta);
+
+        for (i, body) in self.bodies.iter_mut().enumerate() {
+            body.vel[0] += 0.5 * (a_old[i][0] + a_new[i][0]) * dt;
+            body.vel[1] += 0.5 * (a_old[i][1] + a_new[i][1]) * dt;
+            body.vel[2] += 0.5 * (a_old[i][2] + a_new[i][2]) * dt;
+            body.pos = new_positions[i];
+        }
+    }
+
+    //classical RK4 over the state (pos, vel); each stage's velocity-rate is the
+    //acceleration recomputed at that stage's intermediate positions
+    #[instrument(skip(self), name = "tick_rk4")]
+    fn step_rk4(&mut self, dt: f64, mode: &Mode, theta: f64) {
+        let n = self.bodies.len();
+        let pos0 = self.positions();
+        let vel0: Vec<[f64; 3]> = self.bodies.iter().map(|b| b.vel).collect();
+
+        let k1_vel = vel0.clone();
+        let k1_acc = self.accelerations_at(&pos0, mode, theta);
+
+        let pos2 = offset(&pos0, &k1_vel, 0.5 * dt);
+        let k2_vel = offset(&vel0, &k1_acc, 0.5 * dt);
+        let k2_acc = self.accelerations_at(&pos2, mode, theta);
+
+        let pos3 = offset(&pos0, &k2_vel, 0.5 * dt);
+        let k3_vel = offset(&vel0, &k2_acc, 0.5 * dt);
+        let k3_acc = self.accelerations_at(&pos3, mode, theta);
+
+        let pos4 = offset(&pos0, &k3_vel, dt);
+        let k4_vel = offset(&vel0, &k3_acc, dt);
+        let k4_acc = self.accelerations_at(&pos4, mode, theta);
+
+        for i in 0..n {
+            let body = &mut self.bodies[i];
+            for axis in 0..3 {
+                let pos_rate = k1_vel[i][axis] + 2.0 * k2_vel[i][axis] + 2.0 * k3_vel[i][axis] + k4_vel[i][axis];
+                let vel_rate = k1_acc[i][axis] + 2.0 * k2_acc[i][axis] + 2.0 * k3_acc[i][axis] + k4_acc[i][axis];
+
+                body.pos[axis] = pos0[i][axis] + dt / 6.0 * pos_rate;
+                body.vel[axis] = vel0[i][axis] + dt / 6.0 * vel_rate;
+            }
+        }
+    }
+}
+
+//state + dt*rate, used to build the intermediate positions/velocities for each RK4 stage
+fn offset(base: &[[f64; 3]], rate: &[[f64; 3]], dt: f64) -> Vec<[f64; 3]> {
+    base.iter()
+        .zip(rate.iter())
+        .map(|(b, r)| [b[0] + r[0] * dt, b[1] + r[1] * dt, b[2] + r[2] * dt])
+        .collect()
+}
+
+//softened gravitational acceleration at target_pos caused by a point mass at source_pos
+#[inline(always)]
+fn softened_force(target_pos: [f64; 3], source_pos: [f64; 3], source_mass: f64, g_const: f64, softening: f64) -> [f64; 3] {
+    let dx = source_pos[0] - target_pos[0];
+    let dy = source_pos[1] - target_pos[1];
+    let dz = source_pos[2] - target_pos[2];
+
+    let dist_sq = dx * dx + dy * dy + dz * dz + softening;
+    let dist = dist_sq.sqrt();
+    let f = (g_const * source_mass) / dist_sq;
+
+    [f * dx / dist, f * dy / dist, f * dz / dist]
+}
+
+//below this recursion depth, remaining bodies are merged into a single leaf pseudo-body
+//instead of being split further. Without this, bodies sitting at (near-)identical positions
+//would keep landing in the same octant forever as half_width keeps halving, recursing without
+//bound; 32 halvings of any realistic bounding box is already far below floating-point
+//precision, so real separations always resolve long before hitting the cap.
+const MAX_OCTREE_DEPTH: usize = 32;
+
+//octree over the bodies' bounding cube; each node tracks total mass and center-of-mass so a
+//whole distant subtree can stand in for a single pseudo-body during traversal
+struct Octree {
+    half_width: f64,
+    mass: f64,
+    com: [f64; 3],
+    //the body indices held at a leaf: exactly one in the common case, or several when
+    //MAX_OCTREE_DEPTH forced (near-)coincident bodies to merge into one pseudo-body; empty
+    //once the node has children, or when it's a childless empty node
+    leaf_bodies: Vec<usize>,
+    children: Option<Box<[Octree; 8]>>,
+}
+
+impl Octree {
+    fn empty(half_width: f64) -> Self {
+        Octree {
+            half_width,
+            mass: 0.0,
+            com: [0.0; 3],
+            leaf_bodies: Vec::new(),
+            children: None,
+        }
+    }
+
+    fn build(positions: &[[f64; 3]], masses: &[f64]) -> Self {
+        let mut min = [f64::INFINITY; 3];
+        let mut max = [f64::NEG_INFINITY; 3];
+        for pos in positions {
+            for k in 0..3 {
+                min[k] = min[k].min(pos[k]);
+                max[k] = max[k].max(pos[k]);
+            }
+        }
+
+        let center = [
+            (min[0] + max[0]) / 2.0,
+            (min[1] + max[1]) / 2.0,
+            (min[2] + max[2]) / 2.0,
+        ];
+        //pad slightly so bodies sitting exactly on the bounding box still fall inside a child
+        let extent = (max[0] - min[0]).max(max[1] - min[1]).max(max[2] - min[2]).max(1e-9);
+        let half_width = extent / 2.0 * 1.01;
+
+        let indices: Vec<usize> = (0..positions.len()).collect();
+        Self::build_node(center, half_width, &indices, positions, masses, 0)
+    }
+
+    //builds one node from the given set of body indices, splitting into 8 octants and
+    //recursing. The 8 children are independent subtrees (none shares mutable state with its
+    //siblings), so they're built with rayon instead of the sequential insert-one-at-a-time
+    //a mutable tree would require.
+    fn build_node(
+        center: [f64; 3],
+        half_width: f64,
+        indices: &[usize],
+        positions: &[[f64; 3]],
+        masses: &[f64],
+        depth: usize,
+    ) -> Self {
+        let mut node = Octree::empty(half_width);
+        if indices.is_empty() {
+            return node;
+        }
+
+        let mut mass = 0.0;
+        let mut com = [0.0; 3];
+        for &idx in indices {
+            let m = masses[idx];
+            com[0] += positions[idx][0] * m;
+            com[1] += positions[idx][1] * m;
+            com[2] += positions[idx][2] * m;
+            mass += m;
+        }
+        com[0] /= mass;
+        com[1] /= mass;
+        com[2] /= mass;
+        node.mass = mass;
+        node.com = com;
+
+        if indices.len() == 1 || depth >= MAX_OCTREE_DEPTH {
+            node.leaf_bodies = indices.to_vec();
+            return node;
+        }
+
+        let mut buckets: [Vec<usize>; 8] = std::array::from_fn(|_| Vec::new());
+        for &idx in indices {
+            buckets[Self::octant_for(center, positions[idx])].push(idx);
+        }
+
+        let children: Vec<Octree> = buckets
+            .into_par_iter()
+            .enumerate()
+            .map(|(octant, bucket)| {
+                Self::build_node(
+                    Self::child_center(center, half_width, octant),
+                    half_width / 2.0,
+                    &bucket,
+                    positions,
+                    masses,
+                    depth + 1,
+                )
+            })
+            .collect();
+
+        node.children = Some(Box::new(children.try_into().unwrap_or_else(|_| unreachable!())));
+        node
+    }
+
+    //which of the 8 children pos falls into, relative to this node's center
+    fn octant_for(center: [f64; 3], pos: [f64; 3]) -> usize {
+        let mut octant = 0;
+        if pos[0] >= center[0] {
+            octant |= 1;
+        }
+        if pos[1] >= center[1] {
+            octant |= 2;
+        }
+        if pos[2] >= center[2] {
+            octant |= 4;
+        }
+        octant
+    }
+
+    fn child_center(center: [f64; 3], half_width: f64, octant: usize) -> [f64; 3] {
+        let offset = half_width / 2.0;
+        [
+            center[0] + if octant & 1 != 0 { offset } else { -offset },
+            center[1] + if octant & 2 != 0 { offset } else { -offset },
+            center[2] + if octant & 4 != 0 { offset } else { -offset },
+        ]
+    }
+
+    //acceleration on target_idx (sitting at target_pos) from this node and its descendants
+    fn acceleration_on(&self, target_pos: [f64; 3], target_idx: usize, theta: f64, g_const: f64, softening: f64) -> [f64; 3] {
+        if self.mass == 0.0 {
+            return [0.0; 3];
+        }
+
+        match &self.children {
+            None => {
+                if self.leaf_bodies.contains(&target_idx) {
+                    [0.0; 3]
+                } else {
+                    softened_force(target_pos, self.com, self.mass, g_const, softening)
+                }
+            }
+            Some(children) => {
+                let dx = self.com[0] - target_pos[0];
+                let dy = self.com[1] - target_pos[1];
+                let dz = self.com[2] - target_pos[2];
+                let dist = (dx * dx + dy * dy + dz * dz).sqrt();
+                let cell_width = self.half_width * 2.0;
+
+                if dist > 0.0 && cell_width / dist < theta {
+                    softened_force(target_pos, self.com, self.mass, g_const, softening)
+                } else {
+                    let mut acc = [0.0; 3];
+                    for child in children.iter() {
+                        let a = child.acceleration_on(target_pos, target_idx, theta, g_const, softening);
+                        acc[0] += a[0];
+                        acc[1] += a[1];
+                        acc[2] += a[2];
+                    }
+                    acc
+                }
+            }
+        }
+    }
+}
+
+//CUDA-backed acceleration kernel, compiled only when the `gpu` feature is on; keeps CUDA out
+//of the default dependency tree entirely, following the cuda-feature pattern used by other
+//Rust numeric crates
+#[cfg(feature = "gpu")]
+mod gpu {
+    use cudarc::driver::{CudaDevice, LaunchAsync, LaunchConfig};
+    use cudarc::nvrtc::compile_ptx;
+
+    const ACCEL_KERNEL_SRC: &str = r#"
+extern "C" __global__ void accel_kernel(
+    const double* xs, const double* ys, const double* zs, const double* masses,
+    double* ax, double* ay, double* az,
+    double g_const, double softening, int n
+) {
+    int i = blockIdx.x * blockDim.x + threadIdx.x;
+    if (i >= n) return;
+
+    double tx = xs[i], ty = ys[i], tz = zs[i];
+    double acc_x = 0.0, acc_y = 0.0, acc_z = 0.0;
+
+    for (int j = 0; j < n; j++) {
+        if (j == i) continue;
+        double dx = xs[j] - tx;
+        double dy = ys[j] - ty;
+        double dz = zs[j] - tz;
+        double dist_sq = dx * dx + dy * dy + dz * dz + softening;
+        double dist = sqrt(dist_sq);
+        double f = (g_const * masses[j]) / dist_sq;
+        acc_x += f * dx / dist;
+        acc_y += f * dy / dist;
+        acc_z += f * dz / dist;
+    }
+
+    ax[i] = acc_x;
+    ay[i] = acc_y;
+    az[i] = acc_z;
+}
+"#;
+
+    //uploads the structure-of-arrays layout once, launches one thread per target body, and
+    //copies the accelerations back; returns None if no CUDA device is present
+    pub fn compute_accelerations(
+        positions: &[[f64; 3]],
+        masses: &[f64],
+        g_const: f64,
+        softening: f64,
+    ) -> Option<Vec<[f64; 3]>> {
+        let n = positions.len();
+        let device = CudaDevice::new(0).ok()?;
+
+        let ptx = compile_ptx(ACCEL_KERNEL_SRC).ok()?;
+        device.load_ptx(ptx, "accel_kernel_module", &["accel_kernel"]).ok()?;
+        let kernel = device.get_func("accel_kernel_module", "accel_kernel")?;
+
+        let mut xs = vec![0.0; n];
+        let mut ys = vec![0.0; n];
+        let mut zs = vec![0.0; n];
+        for (i, pos) in positions.iter().enumerate() {
+            xs[i] = pos[0];
+            ys[i] = pos[1];
+            zs[i] = pos[2];
+        }
+
+        let xs_dev = device.htod_copy(xs).ok()?;
+        let ys_dev = device.htod_copy(ys).ok()?;
+        let zs_dev = device.htod_copy(zs).ok()?;
+        let masses_dev = device.htod_copy(masses.to_vec()).ok()?;
+        let mut ax_dev = device.alloc_zeros::<f64>(n).ok()?;
+        let mut ay_dev = device.alloc_zeros::<f64>(n).ok()?;
+        let mut az_dev = device.alloc_zeros::<f64>(n).ok()?;
+
+        let config = LaunchConfig::for_num_elems(n as u32);
+        unsafe {
+            kernel.launch(
+                config,
+                (
+                    &xs_dev,
+                    &ys_dev,
+                    &zs_dev,
+                    &masses_dev,
+                    &mut ax_dev,
+                    &mut ay_dev,
+                    &mut az_dev,
+                    g_const,
+                    softening,
+                    n as i32,
+                ),
+            )
+        }
+        .ok()?;
+
+        let ax = device.dtoh_sync_copy(&ax_dev).ok()?;
+        let ay = device.dtoh_sync_copy(&ay_dev).ok()?;
+        let az = device.dtoh_sync_copy(&az_dev).ok()?;
+
+        Some((0..n).map(|i| [ax[i], ay[i], az[i]]).collect())
+    }
 }
 
 fn main() {
@@ -168,25 +786,98 @@ fn main() {
     info!("Initializing Simulation in {:?} mode...", args.mode);
     let mut universe = Universe::new(args.count);
 
+    let energy_0 = args.energy.then(|| universe.total_energy());
+    let momentum_0 = universe.total_momentum();
+    match energy_0 {
+        Some(e) => info!(
+            "tick 0: energy={:.6e} momentum=[{:.3e}, {:.3e}, {:.3e}]",
+            e, momentum_0[0], momentum_0[1], momentum_0[2]
+        ),
+        None => info!("tick 0: momentum=[{:.3e}, {:.3e}, {:.3e}]", momentum_0[0], momentum_0[1], momentum_0[2]),
+    }
+
+    let initial_positions = universe.positions();
+    let initial_velocities = universe.velocities();
+    let state_scale = universe.state_scale().max(1e-12);
+    let mut has_diverged = false;
+    let mut recurrence_period: Option<usize> = None;
+
     let start_time = Instant::now();
     let dt = 0.01;
 
     for i in 0..args.ticks {
         let _span = span!(Level::INFO, "sim_step", tick = i).entered();
-        match args.mode {
-            Mode::Parallel => universe.step_parallel(dt),
-            Mode::Serial => universe.step_serial(dt),
+        universe.step(dt, &args.mode, &args.integrator, args.theta);
+
+        let should_report = args
+            .report_interval
+            .is_some_and(|interval| interval > 0 && (i + 1) % interval == 0);
+        if should_report {
+            let momentum = universe.total_momentum();
+            match args.energy.then(|| universe.total_energy()) {
+                Some(energy) => info!(
+                    "tick {}: energy={:.6e} momentum=[{:.3e}, {:.3e}, {:.3e}]",
+                    i + 1,
+                    energy,
+                    momentum[0],
+                    momentum[1],
+                    momentum[2]
+                ),
+                None => info!(
+                    "tick {}: momentum=[{:.3e}, {:.3e}, {:.3e}]",
+                    i + 1,
+                    momentum[0],
+                    momentum[1],
+                    momentum[2]
+                ),
+            }
+        }
+
+        if args.detect_recurrence && recurrence_period.is_none() {
+            let distance = universe.state_distance(&initial_positions, &initial_velocities) / state_scale;
+            if distance > args.recurrence_tol {
+                has_diverged = true;
+            } else if has_diverged {
+                recurrence_period = Some(i + 1);
+            }
         }
     }
 
     let duration = start_time.elapsed();
     let per_tick = duration / args.ticks as u32;
 
+    let momentum_final = universe.total_momentum();
+    let energy_drift = energy_0.map(|e0| {
+        let energy_final = universe.total_energy();
+        info!(
+            "tick {}: energy={:.6e} momentum=[{:.3e}, {:.3e}, {:.3e}]",
+            args.ticks, energy_final, momentum_final[0], momentum_final[1], momentum_final[2]
+        );
+        (energy_final - e0).abs() / e0.abs()
+    });
+    if energy_drift.is_none() {
+        info!(
+            "tick {}: momentum=[{:.3e}, {:.3e}, {:.3e}]",
+            args.ticks, momentum_final[0], momentum_final[1], momentum_final[2]
+        );
+    }
+
     println!("\n--- RESULTS ---");
-    println!("Mode:       {:?}", args.mode);
-    println!("Bodies:     {}", args.count);
-    println!("Total Time: {:.2?}", duration);
-    println!("Avg Tick:   {:.2?}", per_tick);
+    println!("Mode:        {:?}", args.mode);
+    println!("Integrator:  {:?}", args.integrator);
+    println!("Bodies:      {}", args.count);
+    println!("Total Time:  {:.2?}", duration);
+    println!("Avg Tick:    {:.2?}", per_tick);
+    match energy_drift {
+        Some(drift) => println!("Energy Drift: {:.6e}", drift),
+        None => println!("Energy Drift: skipped (pass --energy to compute)"),
+    }
+    if args.detect_recurrence {
+        match recurrence_period {
+            Some(period) => println!("Recurrence:  period detected at tick {}", period),
+            None => println!("Recurrence:  no recurrence within {} ticks", args.ticks),
+        }
+    }
     println!("----------------\n");
 }
 
@@ -201,9 +892,9 @@ mod tests {
         universe_parallel.bodies = universe_serial.bodies.clone();
 
         let dt = 0.01;
-        
-        universe_serial.step_serial(dt);
-        universe_parallel.step_parallel(dt);
+
+        universe_serial.step_euler(dt, &Mode::Serial, 0.5);
+        universe_parallel.step_euler(dt, &Mode::Parallel, 0.5);
 
         //for checking race conditions.
         for i in 0..universe_serial.bodies.len() {
@@ -219,4 +910,177 @@ mod tests {
             assert!(diff_z < 1e-10, "Drift detected in Z at index {}", i);
         }
     }
+
+    #[test]
+    fn test_simd_matches_serial() {
+        let mut universe_serial = Universe::new(103);
+        let mut universe_simd = Universe::new(103);
+        universe_simd.bodies = universe_serial.bodies.clone();
+
+        let dt = 0.01;
+
+        universe_serial.step_euler(dt, &Mode::Serial, 0.5);
+        universe_simd.step_euler(dt, &Mode::Simd, 0.5);
+
+        //lane reordering changes summation order, so use a looser tolerance than the scalar paths
+        for i in 0..universe_serial.bodies.len() {
+            let s_pos = universe_serial.bodies[i].pos;
+            let simd_pos = universe_simd.bodies[i].pos;
+
+            for axis in 0..3 {
+                let diff = (s_pos[axis] - simd_pos[axis]).abs();
+                assert!(diff < 1e-6, "SIMD drift detected on axis {} at index {}", axis, i);
+            }
+        }
+    }
+
+    #[cfg(feature = "gpu")]
+    #[test]
+    fn test_gpu_matches_serial() {
+        let mut universe_serial = Universe::new(101);
+        let mut universe_gpu = Universe::new(101);
+        universe_gpu.bodies = universe_serial.bodies.clone();
+
+        let dt = 0.01;
+
+        universe_serial.step_euler(dt, &Mode::Serial, 0.5);
+        universe_gpu.step_euler(dt, &Mode::Gpu, 0.5);
+
+        //kernel accumulation order differs from the scalar host path, so use a looser tolerance
+        for i in 0..universe_serial.bodies.len() {
+            let s_pos = universe_serial.bodies[i].pos;
+            let gpu_pos = universe_gpu.bodies[i].pos;
+
+            for axis in 0..3 {
+                let diff = (s_pos[axis] - gpu_pos[axis]).abs();
+                assert!(diff < 1e-5, "GPU drift detected on axis {} at index {}", axis, i);
+            }
+        }
+    }
+
+    #[test]
+    fn test_integrators_produce_finite_motion() {
+        let dt = 0.01;
+
+        for integrator in [Integrator::Euler, Integrator::Verlet, Integrator::Rk4] {
+            let mut universe = Universe::new(20);
+            for _ in 0..5 {
+                universe.step(dt, &Mode::Serial, &integrator, 0.5);
+            }
+
+            for body in &universe.bodies {
+                for axis in 0..3 {
+                    assert!(body.pos[axis].is_finite(), "non-finite position under {:?}", integrator);
+                    assert!(body.vel[axis].is_finite(), "non-finite velocity under {:?}", integrator);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_initial_momentum_is_zeroed() {
+        let universe = Universe::new(200);
+        let momentum = universe.total_momentum();
+
+        for (axis, component) in momentum.iter().enumerate() {
+            assert!(component.abs() < 1e-6, "nonzero initial momentum on axis {}", axis);
+        }
+    }
+
+    #[test]
+    fn test_verlet_conserves_energy_better_than_euler() {
+        let mut universe_euler = Universe::new(50);
+        let mut universe_verlet = Universe::new(50);
+        universe_verlet.bodies = universe_euler.bodies.clone();
+
+        let energy_0 = universe_euler.total_energy();
+        let dt = 0.01;
+
+        for _ in 0..200 {
+            universe_euler.step(dt, &Mode::Serial, &Integrator::Euler, 0.5);
+            universe_verlet.step(dt, &Mode::Serial, &Integrator::Verlet, 0.5);
+        }
+
+        let euler_drift = (universe_euler.total_energy() - energy_0).abs() / energy_0.abs();
+        let verlet_drift = (universe_verlet.total_energy() - energy_0).abs() / energy_0.abs();
+
+        assert!(
+            verlet_drift < euler_drift,
+            "expected Verlet drift ({}) to be smaller than Euler drift ({})",
+            verlet_drift,
+            euler_drift
+        );
+    }
+
+    #[test]
+    fn test_barnes_hut_matches_exact_for_small_theta() {
+        let universe = Universe::new(64);
+        let positions = universe.positions();
+
+        let exact = universe.accelerations_serial(&positions);
+        let approx = universe.accelerations_barnes_hut(&positions, 0.1);
+
+        for i in 0..exact.len() {
+            for axis in 0..3 {
+                let e = exact[i][axis];
+                let a = approx[i][axis];
+                let rel_err = (e - a).abs() / e.abs().max(1e-9);
+                assert!(
+                    rel_err < 0.05,
+                    "Barnes-Hut diverged from exact at body {} axis {}: exact={}, approx={}",
+                    i,
+                    axis,
+                    e,
+                    a
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_recurrence_detects_circular_orbit_period() {
+        //a two-body circular orbit: analytic period is 2*pi*sqrt(r^3 / (G*M))
+        let g_const: f64 = 1.0;
+        let mass: f64 = 1_000_000.0;
+        let r: f64 = 100.0;
+        let v = (g_const * mass / r).sqrt();
+
+        let mut universe = Universe {
+            bodies: vec![
+                Body { id: 0, pos: [0.0, 0.0, 0.0], vel: [0.0, 0.0, 0.0], mass },
+                Body { id: 1, pos: [r, 0.0, 0.0], vel: [0.0, v, 0.0], mass: 1.0 },
+            ],
+            g_const,
+            softening: 1e-5,
+        };
+
+        let initial_positions = universe.positions();
+        let initial_velocities = universe.velocities();
+        let state_scale = universe.state_scale();
+
+        let dt = 0.01;
+        let period = 2.0 * std::f64::consts::PI * (r.powi(3) / (g_const * mass)).sqrt();
+        let ticks = (period / dt * 1.2) as usize;
+
+        let mut has_diverged = false;
+        let mut recurrence_period = None;
+        for i in 0..ticks {
+            universe.step(dt, &Mode::Serial, &Integrator::Rk4, 0.5);
+            let distance = universe.state_distance(&initial_positions, &initial_velocities) / state_scale;
+            if distance > 1e-2 {
+                has_diverged = true;
+            } else if has_diverged && recurrence_period.is_none() {
+                recurrence_period = Some(i + 1);
+            }
+        }
+
+        let detected = recurrence_period.expect("expected the circular orbit to recur");
+        let detected_time = detected as f64 * dt;
+        assert!(
+            (detected_time - period).abs() < period * 0.1,
+            "detected period {} too far from analytic period {}",
+            detected_time,
+            period
+        );
+    }
 }
\ No newline at end of file